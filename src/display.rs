@@ -1,31 +1,72 @@
+use std::borrow::Cow;
 use std::fmt;
-use std::fmt::{Display};
+use std::fmt::{Display, Write as FmtWrite};
+use std::io;
+use std::io::Write as IoWrite;
 use std::ops::Deref;
 
 use ansi::RESET;
 use difference::Difference;
 use style::{Style, Colour};
+use write::{AnyWrite, FmtAdapter, IoAdapter};
 
 
 /// An `ANSIDisplay` includes a generic Display type and a `Style` to
-/// display it.
-#[derive(PartialEq, Debug, Clone)]
-pub struct ANSIDisplay<'a, T: Display + ?Sized>
+/// display it. The payload is held in a `Cow`, so it can either borrow
+/// from the caller or own its value outright — see `to_owned_display`.
+#[derive(PartialEq)]
+pub struct ANSIDisplay<'a, T: ToOwned + Display + ?Sized>
 {
     style: Style,
-    display: &'a T
+    display: Cow<'a, T>
+}
+
+// A naive `#[derive(Clone)]` would add a blanket `T: Clone` bound, which
+// `str` (the crate's most common payload) never satisfies. `Cow<'a, T>`
+// itself is `Clone` given the weaker `T::Owned: Clone`, so implement it
+// by hand with that bound instead, matching `to_owned_display`.
+impl<'a, T: ToOwned + Display + ?Sized> Clone for ANSIDisplay<'a, T>
+where
+    <T as ToOwned>::Owned: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            style: self.style.clone(),
+            display: self.display.clone(),
+        }
+    }
 }
 
-impl<'a, T: Display + ?Sized> From<&'a T> for ANSIDisplay<'a, T> {
+// Likewise, a derived `Debug` would need `T::Owned: Debug` for the `Cow`
+// field, so format the payload through `&T` instead.
+impl<'a, T: ToOwned + Display + fmt::Debug + ?Sized> fmt::Debug for ANSIDisplay<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ANSIDisplay")
+            .field("style", &self.style)
+            .field("display", &&*self.display)
+            .finish()
+    }
+}
+
+impl<'a, T: ToOwned + Display + ?Sized> From<&'a T> for ANSIDisplay<'a, T> {
     fn from(input: &'a T) -> Self {
         Self {
             style:  Style::default(),
-            display: input
+            display: Cow::Borrowed(input)
+        }
+    }
+}
+
+impl<'a> From<String> for ANSIDisplay<'a, str> {
+    fn from(input: String) -> Self {
+        Self {
+            style:  Style::default(),
+            display: Cow::Owned(input)
         }
     }
 }
 
-impl<'a, T: Display + ?Sized> ANSIDisplay<'a, T> {
+impl<'a, T: ToOwned + Display + ?Sized> ANSIDisplay<'a, T> {
 
     /// Directly access the style
     pub fn style_ref(&self) -> &Style {
@@ -36,9 +77,33 @@ impl<'a, T: Display + ?Sized> ANSIDisplay<'a, T> {
     pub fn style_ref_mut(&mut self) -> &mut Style {
         &mut self.style
     }
+
+    /// Clone the payload into an owned copy if it is currently borrowed,
+    /// returning an `ANSIDisplay` with no dependency on `'a`. This lets a
+    /// styled value outlive the data it was built from, e.g. when
+    /// returning it from a function or storing it in a collection.
+    pub fn to_owned_display(&self) -> ANSIDisplay<'static, T>
+    where
+        <T as ToOwned>::Owned: Clone,
+    {
+        ANSIDisplay {
+            style: self.style.clone(),
+            display: Cow::Owned(self.display.clone().into_owned()),
+        }
+    }
+
+    /// Write the style prefix, the payload and the style suffix to `w`,
+    /// which may be anything implementing `AnyWrite` (a `fmt::Formatter`,
+    /// a `String`, ...; see `ANSIByteDisplay` for the `io::Write` side).
+    pub fn write_to<W: AnyWrite<Wstr = str, Error = fmt::Error> + ?Sized>(&self, w: &mut W) -> Result<(), W::Error> {
+        let mut w = FmtAdapter(w);
+        write!(w, "{}", self.style.prefix())?;
+        write!(w, "{}", &*self.display)?;
+        write!(w, "{}", self.style.suffix())
+    }
 }
 
-impl<'a, T: Display + ?Sized> Deref for ANSIDisplay<'a, T> {
+impl<'a, T: ToOwned + Display + ?Sized> Deref for ANSIDisplay<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &T {
@@ -50,7 +115,221 @@ impl<'a, T: Display + ?Sized> Deref for ANSIDisplay<'a, T> {
 /// A set of `ANSIGenericString`s collected together, in order to be
 /// written with a minimum of control characters.
 #[derive(Debug, PartialEq)]
-pub struct ANSIDisplaySlice<'a, T: Display + ?Sized>(pub &'a [ANSIDisplay<'a, T>]);
+pub struct ANSIDisplaySlice<'a, T: ToOwned + Display + ?Sized>(pub &'a [ANSIDisplay<'a, T>]);
+
+impl<'a, T: ToOwned + Display + ?Sized> ANSIDisplaySlice<'a, T> {
+
+    /// Write the whole slice to `w`, emitting only the minimal control
+    /// codes needed between consecutive styles. See `ANSIDisplay::write_to`.
+    pub fn write_to<W: AnyWrite<Wstr = str, Error = fmt::Error> + ?Sized>(&self, w: &mut W) -> Result<(), W::Error> {
+        use self::Difference::*;
+
+        let mut w = FmtAdapter(w);
+
+        let first = match self.0.first() {
+            None => return Ok(()),
+            Some(f) => f,
+        };
+
+        write!(w, "{}", first.style.prefix())?;
+        write!(w, "{}", &*first.display)?;
+
+        for window in self.0.windows(2) {
+            match Difference::between(&window[0].style, &window[1].style) {
+                ExtraStyles(style) => write!(w, "{}", style.prefix())?,
+                Reset              => write!(w, "{}{}", RESET, window[1].style.prefix())?,
+                NoDifference       => {/* Do nothing! */},
+            }
+
+            write!(w, "{}", &*window[1].display)?;
+        }
+
+        // Write the final reset string after all of the ANSIStrings have been
+        // written, *except* if the last one has no styles, because it would
+        // have already been written by this point.
+        if let Some(last) = self.0.last() {
+            if !last.style.is_plain() {
+                write!(w, "{}", RESET)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render this slice's payloads with no control codes at all,
+    /// returning the plain text a terminal would actually show.
+    pub fn unstyle(&self) -> String {
+        let mut buf = String::new();
+
+        for ansi in self.0 {
+            write!(buf, "{}", &*ansi.display).expect("formatting into a String never fails");
+        }
+
+        buf
+    }
+
+    /// The number of `char`s this slice will occupy on screen, i.e. its
+    /// length with all control codes stripped. Useful for layout code
+    /// (tables, progress bars, padding) that needs the rendered width.
+    pub fn unstyled_len(&self) -> usize {
+        let mut scratch = String::new();
+        let mut len = 0;
+
+        for ansi in self.0 {
+            scratch.clear();
+            write!(scratch, "{}", &*ansi.display).expect("formatting into a String never fails");
+            len += scratch.chars().count();
+        }
+
+        len
+    }
+}
+
+// ---- gradient painting ----
+
+/// A 24-bit RGB colour, used as a `gradient` endpoint. Unlike `Colour`,
+/// every value of this type is a valid RGB triple, so there's no
+/// non-colour variant for `gradient` to reject at runtime.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl From<Rgb> for Colour {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        Colour::RGB(r, g, b)
+    }
+}
+
+impl<'a, T: ToOwned + Display> ANSIDisplaySlice<'a, T> {
+
+    /// Paint `items` with a foreground colour that sweeps linearly from
+    /// `start` to `end`, one step per element, in integer RGB space.
+    /// With a single item, `start` is used as-is.
+    ///
+    /// The result is a `Vec` rather than an `ANSIDisplaySlice` directly,
+    /// since the latter only ever borrows its elements; build the slice
+    /// from it as usual: `ANSIDisplaySlice(&ANSIDisplaySlice::gradient(...))`.
+    #[must_use]
+    pub fn gradient(items: &'a [T], start: Rgb, end: Rgb) -> Vec<ANSIDisplay<'a, T>> {
+        let Rgb(sr, sg, sb) = start;
+        let Rgb(er, eg, eb) = end;
+        let last = items.len().saturating_sub(1);
+
+        items.iter().enumerate().map(|(i, item)| {
+            let colour: Colour = if last == 0 {
+                start.into()
+            } else {
+                let t = i as f64 / last as f64;
+                Rgb(
+                    lerp_channel(sr, er, t),
+                    lerp_channel(sg, eg, t),
+                    lerp_channel(sb, eb, t),
+                ).into()
+            };
+
+            colour.paint(item)
+        }).collect()
+    }
+}
+
+fn lerp_channel(start: u8, end: u8, t: f64) -> u8 {
+    let value = start as f64 + (end as f64 - start as f64) * t;
+    value.round().max(0.0).min(255.0) as u8
+}
+
+// ---- byte-string display ----
+
+/// Like `ANSIDisplay`, but wraps a raw byte payload instead of a
+/// `Display` value, so it can be streamed straight to an `io::Write`
+/// sink (a socket, a file, ...) without any UTF-8 validation or
+/// intermediate `String` allocation.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ANSIByteDisplay<'a>
+{
+    style: Style,
+    display: &'a [u8]
+}
+
+impl<'a> From<&'a [u8]> for ANSIByteDisplay<'a> {
+    fn from(input: &'a [u8]) -> Self {
+        Self {
+            style:  Style::default(),
+            display: input
+        }
+    }
+}
+
+impl<'a> ANSIByteDisplay<'a> {
+
+    /// Directly access the style
+    pub fn style_ref(&self) -> &Style {
+        &self.style
+    }
+
+    /// Directly access the style mutably
+    pub fn style_ref_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    /// Write the style prefix, the raw payload and the style suffix to
+    /// `w`, which may be anything implementing `AnyWrite` over `io::Write`
+    /// (a `File`, a `TcpStream`, a `Vec<u8>`, ...).
+    pub fn write_to<W: AnyWrite<Wstr = [u8], Error = io::Error> + ?Sized>(&self, w: &mut W) -> Result<(), W::Error> {
+        let mut w = IoAdapter(w);
+        write!(w, "{}", self.style.prefix())?;
+        w.write_all(self.display)?;
+        write!(w, "{}", self.style.suffix())
+    }
+}
+
+impl<'a> Deref for ANSIByteDisplay<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.display
+    }
+}
+
+/// A set of `ANSIByteDisplay`s collected together, in order to be
+/// written with a minimum of control characters.
+#[derive(Debug, PartialEq)]
+pub struct ANSIByteDisplaySlice<'a>(pub &'a [ANSIByteDisplay<'a>]);
+
+impl<'a> ANSIByteDisplaySlice<'a> {
+
+    /// Write the whole slice to `w`, emitting only the minimal control
+    /// codes needed between consecutive styles. See `ANSIDisplaySlice::write_to`.
+    pub fn write_to<W: AnyWrite<Wstr = [u8], Error = io::Error> + ?Sized>(&self, w: &mut W) -> Result<(), W::Error> {
+        use self::Difference::*;
+
+        let mut w = IoAdapter(w);
+
+        let first = match self.0.first() {
+            None => return Ok(()),
+            Some(f) => f,
+        };
+
+        write!(w, "{}", first.style.prefix())?;
+        w.write_all(first.display)?;
+
+        for window in self.0.windows(2) {
+            match Difference::between(&window[0].style, &window[1].style) {
+                ExtraStyles(style) => write!(w, "{}", style.prefix())?,
+                Reset              => write!(w, "{}{}", RESET, window[1].style.prefix())?,
+                NoDifference       => {/* Do nothing! */},
+            }
+
+            w.write_all(window[1].display)?;
+        }
+
+        if let Some(last) = self.0.last() {
+            if !last.style.is_plain() {
+                write!(w, "{}", RESET)?;
+            }
+        }
+
+        Ok(())
+    }
+}
 
 // ---- paint functions ----
 
@@ -58,8 +337,18 @@ impl Style {
 
     /// Paints the given text with this colour, returning an ANSI string.
     #[must_use]
-    pub fn paint<T: Display + ?Sized>(self, input: &T) -> ANSIDisplay<T> {
+    pub fn paint<T: ToOwned + Display + ?Sized>(self, input: &T) -> ANSIDisplay<T> {
         ANSIDisplay {
+            display: Cow::Borrowed(input),
+            style:  self,
+        }
+    }
+
+    /// Paints the given bytes with this style, returning a byte-oriented
+    /// ANSI string suitable for writing straight to an `io::Write` sink.
+    #[must_use]
+    pub fn paint_bytes<'a>(self, input: &'a [u8]) -> ANSIByteDisplay<'a> {
+        ANSIByteDisplay {
             display: input,
             style:  self,
         }
@@ -78,8 +367,18 @@ impl Colour {
     /// println!("{}", Blue.paint("da ba dee"));
     /// ```
     #[must_use]
-    pub fn paint<T: Display + ?Sized>(self, input: &T) -> ANSIDisplay<T> {
+    pub fn paint<T: ToOwned + Display + ?Sized>(self, input: &T) -> ANSIDisplay<T> {
         ANSIDisplay {
+            display: Cow::Borrowed(input),
+            style:  self.normal(),
+        }
+    }
+
+    /// Paints the given bytes with this colour, returning a byte-oriented
+    /// ANSI string suitable for writing straight to an `io::Write` sink.
+    #[must_use]
+    pub fn paint_bytes<'a>(self, input: &'a [u8]) -> ANSIByteDisplay<'a> {
+        ANSIByteDisplay {
             display: input,
             style:  self.normal(),
         }
@@ -89,48 +388,17 @@ impl Colour {
 
 // ---- writers for individual ANSI strings ----
 
-impl<'a, T: Display + ?Sized> Display for ANSIDisplay<'a,  T> {
+impl<'a, T: ToOwned + Display + ?Sized> Display for ANSIDisplay<'a,  T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.style.prefix())?;
-        self.display.fmt(f)?;
-        write!(f, "{}", self.style.suffix())
+        self.write_to(f)
     }
 }
 
 // ---- writers for combined ANSI strings ----
 
-impl<'a, T: Display + ?Sized> Display for ANSIDisplaySlice<'a, T> {
+impl<'a, T: ToOwned + Display + ?Sized> Display for ANSIDisplaySlice<'a, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use self::Difference::*;
-
-        let first = match self.0.first() {
-            None => return Ok(()),
-            Some(f) => f,
-        };
-
-        write!(f, "{}", first.style.prefix())?;
-        first.display.fmt(f)?;
-
-        for window in self.0.windows(2) {
-            match Difference::between(&window[0].style, &window[1].style) {
-                ExtraStyles(style) => write!(f, "{}", style.prefix())?,
-                Reset              => write!(f, "{}{}", RESET, window[1].style.prefix())?,
-                NoDifference       => {/* Do nothing! */},
-            }
-
-            window[1].display.fmt(f)?;
-        }
-
-        // Write the final reset string after all of the ANSIStrings have been
-        // written, *except* if the last one has no styles, because it would
-        // have already been written by this point.
-        if let Some(last) = self.0.last() {
-            if !last.style.is_plain() {
-                write!(f, "{}", RESET)?;
-            }
-        }
-
-        Ok(())
+        self.write_to(f)
     }
 }
 
@@ -148,4 +416,83 @@ mod tests {
         let output = format!("{}", ANSIDisplaySlice( &[ one, two ] ));
         assert_eq!(&*output, "onetwo");
     }
+
+    #[test]
+    fn byte_display_writes_raw_bytes_to_io_write() {
+        let mut sink: Vec<u8> = Vec::new();
+        Style::default().paint_bytes(b"hello").write_to(&mut sink).unwrap();
+        assert_eq!(&*sink, b"hello");
+    }
+
+    #[test]
+    fn byte_display_slice_writes_minimal_control_codes() {
+        use ansi::RESET;
+        use super::super::ANSIByteDisplaySlice;
+
+        let bold_style = Style::default().bold();
+        let italic_style = Style::default().italic();
+
+        let plain = Style::default().paint_bytes(b"one");
+        let bold = bold_style.paint_bytes(b"two");
+        let also_bold = bold_style.paint_bytes(b"three");
+        let italic = italic_style.paint_bytes(b"four");
+
+        let mut sink: Vec<u8> = Vec::new();
+        ANSIByteDisplaySlice( &[ plain, bold, also_bold, italic ] ).write_to(&mut sink).unwrap();
+
+        // plain -> bold is an extra style (just a prefix change), bold ->
+        // bold is no difference at all, bold -> italic drops an attribute
+        // so it must go through a full reset, and the trailing RESET shows
+        // up because the slice doesn't end on a plain style.
+        let expected = format!(
+            "one{}twothree{}{}four{}",
+            bold_style.prefix(), RESET, italic_style.prefix(), RESET
+        );
+        assert_eq!(&*sink, expected.as_bytes());
+    }
+
+    #[test]
+    fn to_owned_display_detaches_from_source() {
+        use super::super::ANSIDisplay;
+
+        let owned: ANSIDisplay<'static, str> = {
+            let local = String::from("borrowed for now");
+            let styled = Style::default().bold().paint(local.as_str());
+            styled.to_owned_display()
+            // `local` and `styled` go out of scope here; `owned` must not.
+        };
+
+        assert_eq!(&*owned, "borrowed for now");
+        assert_eq!(format!("{}", owned), format!("{}", Style::default().bold().paint("borrowed for now")));
+    }
+
+    #[test]
+    fn display_from_owned_string() {
+        use super::super::ANSIDisplay;
+
+        let display: ANSIDisplay<'static, str> = ANSIDisplay::from(String::from("owned"));
+        assert_eq!(&*display, "owned");
+    }
+
+    #[test]
+    fn unstyle_strips_control_codes() {
+        let one = Style::default().bold().paint("one");
+        let two = Style::default().paint("two");
+        let slice = ANSIDisplaySlice( &[ one, two ] );
+        assert_eq!(&*slice.unstyle(), "onetwo");
+        assert_eq!(slice.unstyled_len(), 6);
+    }
+
+    #[test]
+    fn gradient_interpolates_endpoints() {
+        use style::Colour;
+        use super::super::Rgb;
+
+        let items = ["a", "b", "c"];
+        let painted = ANSIDisplaySlice::gradient(&items, Rgb(0, 0, 0), Rgb(100, 0, 0));
+
+        assert_eq!(*painted[0].style_ref(), Colour::RGB(0, 0, 0).normal());
+        assert_eq!(*painted[2].style_ref(), Colour::RGB(100, 0, 0).normal());
+        assert_eq!(*painted[1].style_ref(), Colour::RGB(50, 0, 0).normal());
+    }
 }
@@ -0,0 +1,82 @@
+use std::fmt;
+use std::io;
+
+/// Abstracts over `std::fmt::Write` and `std::io::Write` so the same
+/// rendering code can hand its output to either kind of sink.
+///
+/// `Wstr` is the chunk type a given writer accepts (`str` for
+/// formatter-backed writers, `[u8]` for byte-backed ones) and `Error`
+/// is that writer's native error type, so callers still get the real
+/// `fmt::Error`/`io::Error` back rather than some wrapper.
+pub trait AnyWrite {
+    type Wstr: ?Sized;
+    type Error;
+
+    /// Write a single chunk to the underlying sink.
+    fn write_any_str(&mut self, s: &Self::Wstr) -> Result<(), Self::Error>;
+}
+
+impl<'a> AnyWrite for fmt::Formatter<'a> {
+    type Wstr = str;
+    type Error = fmt::Error;
+
+    fn write_any_str(&mut self, s: &str) -> fmt::Result {
+        self.write_str(s)
+    }
+}
+
+impl AnyWrite for String {
+    type Wstr = str;
+    type Error = fmt::Error;
+
+    fn write_any_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+// `io::Write` sinks get one impl per concrete type as well: a blanket impl
+// over every `io::Write` would conflict with the impls above, since Rust's
+// coherence rules let std add `io::Write` for `String` or `Formatter` later.
+// The types listed below can be passed straight in; any other `io::Write`
+// sink is passed as a `&mut dyn io::Write`.
+macro_rules! impl_any_write_for_io {
+    ($($t:ty),*) => {$(
+        impl AnyWrite for $t {
+            type Wstr = [u8];
+            type Error = io::Error;
+
+            fn write_any_str(&mut self, s: &[u8]) -> io::Result<()> {
+                io::Write::write_all(self, s)
+            }
+        }
+    )*};
+}
+
+impl_any_write_for_io!(Vec<u8>, std::fs::File, std::net::TcpStream, io::Stdout, io::Stderr, dyn io::Write + '_);
+
+/// Adapts an `AnyWrite<Wstr = str>` sink back into `fmt::Write`, so
+/// `display.rs` can format straight into it with `write!` instead of
+/// building an intermediate `String` per fragment.
+pub(crate) struct FmtAdapter<'w, W: AnyWrite<Wstr = str, Error = fmt::Error> + ?Sized>(pub &'w mut W);
+
+impl<'w, W: AnyWrite<Wstr = str, Error = fmt::Error> + ?Sized> fmt::Write for FmtAdapter<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_any_str(s)
+    }
+}
+
+/// Adapts an `AnyWrite<Wstr = [u8]>` sink back into `io::Write`, so
+/// `display.rs` can format escape codes straight into it with `write!`.
+pub(crate) struct IoAdapter<'w, W: AnyWrite<Wstr = [u8], Error = io::Error> + ?Sized>(pub &'w mut W);
+
+impl<'w, W: AnyWrite<Wstr = [u8], Error = io::Error> + ?Sized> io::Write for IoAdapter<'w, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_any_str(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}